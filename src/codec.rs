@@ -0,0 +1,225 @@
+#[cfg(all(not(feature = "std"), feature = "serde"))]
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use crate::dirman::Directory;
+use crate::error::Result;
+use crate::pageman::{MemStorage, Page};
+
+/// A wire format `Page`/`Directory` can be (de)serialized through. Lets the
+/// existing fixed on-disk layout and a `serde`-based compact layout coexist
+/// behind the same interface.
+pub trait Codec {
+    fn encode_page(page: &Page) -> Result<Vec<u8>>;
+    fn decode_page(bytes: &[u8]) -> Result<Page>;
+    fn encode_directory(dir: &Directory) -> Result<Vec<u8>>;
+    fn decode_directory(bytes: &[u8]) -> Result<Directory>;
+}
+
+/// The original hand-rolled fixed-width layout, just re-targeted from a
+/// `File` to an in-memory buffer via `Page`/`Directory`'s shared
+/// `write_to`/`read_from`.
+pub struct FixedLayoutCodec;
+
+impl Codec for FixedLayoutCodec {
+    fn encode_page(page: &Page) -> Result<Vec<u8>> {
+        let mut storage = MemStorage::new();
+        page.write_to(&mut storage)?;
+        Ok(storage.into_bytes())
+    }
+
+    fn decode_page(bytes: &[u8]) -> Result<Page> {
+        Page::read_from(&mut MemStorage::from_bytes(bytes.to_vec()))
+    }
+
+    fn encode_directory(dir: &Directory) -> Result<Vec<u8>> {
+        let mut storage = MemStorage::new();
+        dir.write_to(&mut storage)?;
+        Ok(storage.into_bytes())
+    }
+
+    fn decode_directory(bytes: &[u8]) -> Result<Directory> {
+        Directory::read_from(&mut MemStorage::from_bytes(bytes.to_vec()))
+    }
+}
+
+/// A compact `serde` + `bincode` wire format, for downstream users who want
+/// to embed these structures in their own formats with versioned migration
+/// instead of the fixed byte layout.
+#[cfg(feature = "serde")]
+pub struct BincodeCodec;
+
+#[cfg(feature = "serde")]
+impl Codec for BincodeCodec {
+    fn encode_page(page: &Page) -> Result<Vec<u8>> {
+        bincode::serialize(page).map_err(|e| crate::error::DbError::StringConversion(e.to_string()))
+    }
+
+    fn decode_page(bytes: &[u8]) -> Result<Page> {
+        check_magic(bytes, crate::pageman::PAGE_MAGIC)?;
+        bincode::deserialize(bytes).map_err(|e| crate::error::DbError::StringConversion(e.to_string()))
+    }
+
+    fn encode_directory(dir: &Directory) -> Result<Vec<u8>> {
+        bincode::serialize(dir).map_err(|e| crate::error::DbError::StringConversion(e.to_string()))
+    }
+
+    fn decode_directory(bytes: &[u8]) -> Result<Directory> {
+        check_magic(bytes, crate::dirman::DIRECTORY_MAGIC)?;
+        bincode::deserialize(bytes).map_err(|e| crate::error::DbError::StringConversion(e.to_string()))
+    }
+}
+
+/// `bincode` serializes a `u8` as a single byte with no length prefix, and
+/// the magic field is always a struct's first field, so it's always byte 0
+/// of the encoding -- check it directly instead of relying on bincode to
+/// surface `PageHeader`/`DirectoryHeader`'s custom `Deserialize` error
+/// (which only carries a formatted string, not the structured values).
+#[cfg(feature = "serde")]
+fn check_magic(bytes: &[u8], expected: u8) -> Result<()> {
+    match bytes.first() {
+        Some(&found) if found != expected => Err(crate::error::DbError::InvalidMagic { expected, found }),
+        _ => Ok(()),
+    }
+}
+
+/// Serializes a large fixed-size byte array as a fixed sequence of bytes
+/// (via `serialize_tuple`), not a length-prefixed list, so formats like
+/// `bincode` round-trip it with zero overhead. Only needed for arrays
+/// larger than serde's built-in 32-element array support (e.g. page
+/// content); name/stat fields fit that built-in support directly.
+#[cfg(feature = "serde")]
+pub(crate) mod fixed_bytes {
+    use core::fmt;
+    use core::marker::PhantomData;
+    use serde::de::{SeqAccess, Visitor};
+    use serde::ser::SerializeTuple;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S, const N: usize>(bytes: &[u8; N], serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut tup = serializer.serialize_tuple(N)?;
+        for byte in bytes {
+            tup.serialize_element(byte)?;
+        }
+        tup.end()
+    }
+
+    pub fn deserialize<'de, D, const N: usize>(deserializer: D) -> core::result::Result<[u8; N], D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ArrayVisitor<const N: usize>(PhantomData<[u8; N]>);
+
+        impl<'de, const N: usize> Visitor<'de> for ArrayVisitor<N> {
+            type Value = [u8; N];
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "an array of {} bytes", N)
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> core::result::Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut out = [0u8; N];
+                for (i, slot) in out.iter_mut().enumerate() {
+                    *slot = seq
+                        .next_element()?
+                        .ok_or_else(|| serde::de::Error::invalid_length(i, &self))?;
+                }
+                Ok(out)
+            }
+        }
+
+        deserializer.deserialize_tuple(N, ArrayVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dirman::DirectoryColumn;
+
+    fn sample_page() -> Page {
+        Page::new("page0001", b"hello").unwrap()
+    }
+
+    fn sample_directory() -> Directory {
+        let mut dir = Directory::new("d", Some(alloc::vec![DirectoryColumn::new_int("n")]));
+        let mut pager = crate::pageman::Pager::in_memory().unwrap();
+        dir.add_page(&mut pager, &Page::new("page0001", b"1").unwrap()).unwrap();
+        dir
+    }
+
+    #[test]
+    fn fixed_layout_codec_round_trips_page() {
+        let page = sample_page();
+        let bytes = FixedLayoutCodec::encode_page(&page).unwrap();
+        let decoded = FixedLayoutCodec::decode_page(&bytes).unwrap();
+        assert_eq!(decoded.get_name(), page.get_name());
+        assert_eq!(decoded.get_content(), page.get_content());
+    }
+
+    #[test]
+    fn fixed_layout_codec_round_trips_directory() {
+        let dir = sample_directory();
+        let bytes = FixedLayoutCodec::encode_directory(&dir).unwrap();
+        let decoded = FixedLayoutCodec::decode_directory(&bytes).unwrap();
+        assert_eq!(decoded.get_name(), dir.get_name());
+        assert_eq!(decoded.get_page_names(), dir.get_page_names());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn bincode_codec_round_trips_page() {
+        let page = sample_page();
+        let bytes = BincodeCodec::encode_page(&page).unwrap();
+        let decoded = BincodeCodec::decode_page(&bytes).unwrap();
+        assert_eq!(decoded.get_name(), page.get_name());
+        assert_eq!(decoded.get_content(), page.get_content());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn bincode_codec_round_trips_directory() {
+        let dir = sample_directory();
+        let bytes = BincodeCodec::encode_directory(&dir).unwrap();
+        let decoded = BincodeCodec::decode_directory(&bytes).unwrap();
+        assert_eq!(decoded.get_name(), dir.get_name());
+        assert_eq!(decoded.get_page_names(), dir.get_page_names());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn bincode_codec_reports_invalid_page_magic() {
+        let page = sample_page();
+        let mut bytes = BincodeCodec::encode_page(&page).unwrap();
+        bytes[0] = 0x00;
+
+        match BincodeCodec::decode_page(&bytes) {
+            Err(crate::error::DbError::InvalidMagic { expected, found }) => {
+                assert_eq!(expected, crate::pageman::PAGE_MAGIC);
+                assert_eq!(found, 0x00);
+            }
+            other => panic!("expected InvalidMagic, got {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn bincode_codec_reports_invalid_directory_magic() {
+        let dir = sample_directory();
+        let mut bytes = BincodeCodec::encode_directory(&dir).unwrap();
+        bytes[0] = 0x00;
+
+        match BincodeCodec::decode_directory(&bytes) {
+            Err(crate::error::DbError::InvalidMagic { expected, found }) => {
+                assert_eq!(expected, crate::dirman::DIRECTORY_MAGIC);
+                assert_eq!(found, 0x00);
+            }
+            other => panic!("expected InvalidMagic, got {:?}", other),
+        }
+    }
+}
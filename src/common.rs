@@ -1,3 +1,5 @@
+use alloc::string::{String, ToString};
+
 pub const MIN_PAGE_NAME_SIZE: usize = 8;
 pub const MIN_DIR_NAME_SIZE: usize = 8;
 pub const MIN_COL_NAME_SIZE: usize = 8;
@@ -12,4 +14,4 @@ pub fn string_to_fixed<const N: usize>(s: &str) -> [u8; N] {
 
 pub fn fixed_to_string(bytes: &[u8]) -> String {
     String::from_utf8_lossy(bytes).trim_end_matches('\0').to_string()
-}
\ No newline at end of file
+}
@@ -1,11 +1,19 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
 use std::fs::File;
-use std::io::{Read, Write};
+#[cfg(feature = "std")]
 use std::path::Path;
 use crate::common::{string_to_fixed, fixed_to_string, MIN_DIR_NAME_SIZE, MIN_COL_NAME_SIZE};
 use crate::error::{DbError, Result};
-use crate::pageman::{Page, PAGE_NAME_SIZE};
+use crate::pageman::{Page, Pager, Slot, Storage, PAGE_NAME_SIZE};
 
-pub const DIRECTORY_MAGIC: u8 = 0xCC;
+// Bumped from 0xCC when the fixed `slots` section (one `u32` per page,
+// written right after `names`) was added with no presence flag of its own.
+// Older `.dr` files still carry the old magic, so they're now rejected by
+// the header check below instead of being read with a slots table they
+// don't have.
+pub const DIRECTORY_MAGIC: u8 = 0xCF;
 pub const PAGES_PER_DIRECTORY: usize = 32;
 pub const DIRECTORY_NAME_SIZE: usize = MIN_DIR_NAME_SIZE;
 
@@ -15,13 +23,115 @@ pub const COLUMN_INT: u8 = 0x00;
 pub const COLUMN_FLOAT: u8 = 0x01;
 pub const COLUMN_STRING: u8 = 0x02;
 
+/// Width of the fixed-width min/max encoding used by the zone-map section.
+/// Strings longer than this cannot be bounded exactly, so they fall back to
+/// `PageStats::none()` rather than risk an incorrect skip.
+pub const STATS_VALUE_SIZE: usize = 24;
+pub const STATS_ABSENT: u8 = 0x00;
+pub const STATS_PRESENT: u8 = 0x01;
+
+/// Per-page, per-column min/max bounds used to skip pages during a scan.
+///
+/// `present = false` means the column's value on this page could not be
+/// bounded (parse failure, missing field, oversized string, ...); such a
+/// page is always scanned, never skipped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PageStats {
+    pub present: bool,
+    pub min: [u8; STATS_VALUE_SIZE],
+    pub max: [u8; STATS_VALUE_SIZE],
+}
+
+impl PageStats {
+    fn none() -> Self {
+        PageStats {
+            present: false,
+            min: [0u8; STATS_VALUE_SIZE],
+            max: [0u8; STATS_VALUE_SIZE],
+        }
+    }
+
+    fn exact(bytes: &[u8]) -> Self {
+        let mut value = [0u8; STATS_VALUE_SIZE];
+        let len = bytes.len().min(STATS_VALUE_SIZE);
+        value[..len].copy_from_slice(&bytes[..len]);
+        PageStats {
+            present: true,
+            min: value,
+            max: value,
+        }
+    }
+
+    fn from_field(type_: u8, field: &str) -> Self {
+        match type_ {
+            COLUMN_INT => match field.parse::<i64>() {
+                Ok(v) => Self::exact(&v.to_be_bytes()),
+                Err(_) => Self::none(),
+            },
+            COLUMN_FLOAT => match field.parse::<f64>() {
+                Ok(v) => Self::exact(&v.to_be_bytes()),
+                Err(_) => Self::none(),
+            },
+            COLUMN_STRING => {
+                let bytes = field.as_bytes();
+                if bytes.len() > STATS_VALUE_SIZE {
+                    Self::none()
+                } else {
+                    Self::exact(bytes)
+                }
+            }
+            _ => Self::none(),
+        }
+    }
+}
+
+/// A typed value used to query the zone-map via [`Directory::pages_matching`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnValue {
+    Int(i64),
+    Float(f64),
+    Str(String),
+}
+
+/// Comparison predicate evaluated against a column's per-page `[min, max]`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompareOp {
+    Eq(ColumnValue),
+    Lt(ColumnValue),
+    Gt(ColumnValue),
+    Range(ColumnValue, ColumnValue),
+}
+
+fn decode_stat(type_: u8, bytes: &[u8; STATS_VALUE_SIZE]) -> ColumnValue {
+    match type_ {
+        COLUMN_INT => ColumnValue::Int(i64::from_be_bytes(bytes[..8].try_into().unwrap())),
+        COLUMN_FLOAT => ColumnValue::Float(f64::from_be_bytes(bytes[..8].try_into().unwrap())),
+        _ => ColumnValue::Str(fixed_to_string(bytes)),
+    }
+}
+
+/// Compares two same-typed values; `None` if the types don't match (which
+/// should not happen for bounds decoded with the column's own type, but is
+/// treated as "can't prove anything" rather than panicking).
+fn compare_values(a: &ColumnValue, b: &ColumnValue) -> Option<core::cmp::Ordering> {
+    match (a, b) {
+        (ColumnValue::Int(x), ColumnValue::Int(y)) => x.partial_cmp(y),
+        (ColumnValue::Float(x), ColumnValue::Float(y)) => x.partial_cmp(y),
+        (ColumnValue::Str(x), ColumnValue::Str(y)) => x.partial_cmp(y),
+        _ => None,
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DirectoryColumn {
     pub type_: u8,
     pub name: [u8; COLUMN_NAME_SIZE],
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct DirectoryHeader {
     pub magic: u8,
     pub name: [u8; DIRECTORY_NAME_SIZE],
@@ -29,17 +139,53 @@ pub struct DirectoryHeader {
     pub column_count: u8,
 }
 
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DirectoryHeader {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(serde::Deserialize)]
+        struct Raw {
+            magic: u8,
+            name: [u8; DIRECTORY_NAME_SIZE],
+            page_count: u8,
+            column_count: u8,
+        }
+
+        let raw = Raw::deserialize(deserializer)?;
+        if raw.magic != DIRECTORY_MAGIC {
+            return Err(serde::de::Error::custom(alloc::format!(
+                "invalid directory magic: expected {:#x}, found {:#x}",
+                DIRECTORY_MAGIC, raw.magic
+            )));
+        }
+
+        Ok(DirectoryHeader {
+            magic: raw.magic,
+            name: raw.name,
+            page_count: raw.page_count,
+            column_count: raw.column_count,
+        })
+    }
+}
+
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Directory {
     pub header: DirectoryHeader,
     pub columns: Vec<DirectoryColumn>,
     pub names: Vec<[u8; PAGE_NAME_SIZE]>,
+    /// Pager slot each page was written to, parallel to `names`.
+    pub slots: Vec<Slot>,
+    /// `page_stats[page_idx][col_idx]` bounds that column's value on that page.
+    pub page_stats: Vec<Vec<PageStats>>,
 }
 
 impl Directory {
     pub fn new(name: &str, columns: Option<Vec<DirectoryColumn>>) -> Self {
         let columns_vec = columns.unwrap_or_default();
-        
+
         Directory {
             header: DirectoryHeader {
                 magic: DIRECTORY_MAGIC,
@@ -49,86 +195,225 @@ impl Directory {
             },
             columns: columns_vec,
             names: Vec::with_capacity(PAGES_PER_DIRECTORY),
+            slots: Vec::with_capacity(PAGES_PER_DIRECTORY),
+            page_stats: Vec::with_capacity(PAGES_PER_DIRECTORY),
         }
     }
 
-    pub fn add_page(&mut self, page: &Page) -> Result<()> {
+    /// Allocates a slot for `page` in `pager`, writes it there, and records
+    /// the page's name/slot/zone-map stats in this directory.
+    pub fn add_page<S: Storage>(&mut self, pager: &mut Pager<S>, page: &Page) -> Result<Slot> {
         if self.names.len() >= PAGES_PER_DIRECTORY {
             return Err(DbError::InvalidPageCount(self.names.len() as u8));
         }
-        
+
+        let slot = pager.write_new_page(page)?;
+
         self.names.push(page.header.name);
+        self.slots.push(slot);
+        self.page_stats.push(self.compute_page_stats(page));
         self.header.page_count = self.names.len() as u8;
-        Ok(())
+        Ok(slot)
+    }
+
+    /// Parses the page's `|`-delimited content against `self.columns` and
+    /// computes a min/max bound per column, falling back to "no stats" for
+    /// any column whose field is missing or fails to parse as its type.
+    fn compute_page_stats(&self, page: &Page) -> Vec<PageStats> {
+        let content = String::from_utf8_lossy(page.get_content());
+        let fields: Vec<&str> = content.split('|').collect();
+
+        self.columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| match fields.get(i) {
+                Some(field) => PageStats::from_field(column.type_, field),
+                None => PageStats::none(),
+            })
+            .collect()
+    }
+
+    /// Returns the names of pages whose stored `[min, max]` for `col_name`
+    /// cannot rule out `op`. A page is only omitted when its bounds provably
+    /// fail the predicate; missing/unparseable stats always keep the page.
+    /// An unknown `col_name` matches no pages.
+    pub fn pages_matching(&self, col_name: &str, op: &CompareOp) -> Vec<String> {
+        let col_idx = match self.columns.iter().position(|c| c.get_name() == col_name) {
+            Some(i) => i,
+            None => return Vec::new(),
+        };
+        let type_ = self.columns[col_idx].type_;
+
+        self.get_page_names()
+            .into_iter()
+            .enumerate()
+            .filter(|(page_idx, _)| {
+                match self.page_stats.get(*page_idx).and_then(|row| row.get(col_idx)) {
+                    Some(stats) if stats.present => Self::overlaps(type_, stats, op),
+                    _ => true,
+                }
+            })
+            .map(|(_, name)| name)
+            .collect()
     }
 
+    /// Whether `[stats.min, stats.max]` can still satisfy `op`. Any
+    /// comparison that can't be proven (type mismatch) defaults to `true`
+    /// so the page is kept rather than wrongly skipped.
+    fn overlaps(type_: u8, stats: &PageStats, op: &CompareOp) -> bool {
+        use core::cmp::Ordering;
+
+        let min = decode_stat(type_, &stats.min);
+        let max = decode_stat(type_, &stats.max);
+        let le = |a: &ColumnValue, b: &ColumnValue| compare_values(a, b) != Some(Ordering::Greater);
+
+        match op {
+            CompareOp::Eq(v) => le(&min, v) && le(v, &max),
+            CompareOp::Lt(v) => compare_values(&min, v).is_none_or(|o| o == Ordering::Less),
+            CompareOp::Gt(v) => compare_values(v, &max).is_none_or(|o| o == Ordering::Less),
+            CompareOp::Range(lo, hi) => le(&min, hi) && le(lo, &max),
+        }
+    }
+
+    #[cfg(feature = "std")]
     pub fn save(&self, path: &Path) -> Result<()> {
         let mut file = File::create(path)?;
-        
-        file.write_all(&[self.header.magic])?;
-        file.write_all(&self.header.name)?;
-        file.write_all(&[self.header.page_count])?;
-        file.write_all(&[self.header.column_count])?;
-        
+        self.write_to(&mut file)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut file = File::open(path)?;
+        Self::read_from(&mut file)
+    }
+
+    /// Writes this directory's fixed on-disk layout to any `Storage`, so
+    /// `save` and the in-memory `Codec`s share one encoder.
+    pub(crate) fn write_to<S: Storage>(&self, storage: &mut S) -> Result<()> {
+        storage.write(&[self.header.magic])?;
+        storage.write(&self.header.name)?;
+        storage.write(&[self.header.page_count])?;
+        storage.write(&[self.header.column_count])?;
+
         for column in &self.columns {
-            file.write_all(&[column.type_])?;
-            file.write_all(&column.name)?;
+            storage.write(&[column.type_])?;
+            storage.write(&column.name)?;
         }
-        
+
         for name in &self.names {
-            file.write_all(name)?;
+            storage.write(name)?;
         }
-        
+
+        for slot in &self.slots {
+            storage.write(&slot.to_be_bytes())?;
+        }
+
+        // Zone-map section: a presence flag, followed by one
+        // present/min/max triple per page per column when present. On read
+        // the section is optional, but `.dr` files with the old directory
+        // magic are still rejected by the header check.
+        if self.page_stats.is_empty() {
+            storage.write(&[STATS_ABSENT])?;
+        } else {
+            storage.write(&[STATS_PRESENT])?;
+            for row in &self.page_stats {
+                for stats in row {
+                    storage.write(&[stats.present as u8])?;
+                    storage.write(&stats.min)?;
+                    storage.write(&stats.max)?;
+                }
+            }
+        }
+
         Ok(())
     }
 
-    pub fn load(path: &Path) -> Result<Self> {
-        let mut file = File::open(path)?;
-        
+    /// Reads this directory's fixed on-disk layout from any `Storage`, so
+    /// `load` and the in-memory `Codec`s share one decoder.
+    pub(crate) fn read_from<S: Storage>(storage: &mut S) -> Result<Self> {
         let mut magic_buf = [0u8; 1];
-        file.read_exact(&mut magic_buf)?;
+        storage.read(&mut magic_buf)?;
         let magic = magic_buf[0];
-        
+
         let mut name = [0u8; DIRECTORY_NAME_SIZE];
-        file.read_exact(&mut name)?;
-        
+        storage.read(&mut name)?;
+
         let mut page_count_buf = [0u8; 1];
-        file.read_exact(&mut page_count_buf)?;
+        storage.read(&mut page_count_buf)?;
         let page_count = page_count_buf[0];
-        
+
         let mut column_count_buf = [0u8; 1];
-        file.read_exact(&mut column_count_buf)?;
+        storage.read(&mut column_count_buf)?;
         let column_count = column_count_buf[0];
-        
+
         if magic != DIRECTORY_MAGIC {
             return Err(DbError::InvalidMagic {
                 expected: DIRECTORY_MAGIC,
                 found: magic,
             });
         }
-        
+
         let mut columns = Vec::with_capacity(column_count as usize);
         for _i in 0..column_count {
             let mut type_buf = [0u8; 1];
-            file.read_exact(&mut type_buf)?;
+            storage.read(&mut type_buf)?;
             let type_ = type_buf[0];
-            
+
             let mut col_name = [0u8; COLUMN_NAME_SIZE];
-            file.read_exact(&mut col_name)?;
-            
+            storage.read(&mut col_name)?;
+
             columns.push(DirectoryColumn {
                 type_,
                 name: col_name,
             });
         }
-        
+
         let mut names = Vec::with_capacity(page_count as usize);
         for _i in 0..page_count {
             let mut page_name = [0u8; PAGE_NAME_SIZE];
-            file.read_exact(&mut page_name)?;
+            storage.read(&mut page_name)?;
             names.push(page_name);
         }
-        
+
+        let mut slots = Vec::with_capacity(page_count as usize);
+        for _i in 0..page_count {
+            let mut slot_buf = [0u8; 4];
+            storage.read(&mut slot_buf)?;
+            slots.push(Slot::from_be_bytes(slot_buf));
+        }
+
+        // A file that ends right after the slots table has no zone-map
+        // section; a read failure here just means "no stats", not a
+        // corrupt file.
+        let mut stats_flag_buf = [0u8; 1];
+        let page_stats = match storage.read(&mut stats_flag_buf) {
+            Ok(()) if stats_flag_buf[0] == STATS_PRESENT => {
+                let mut page_stats = Vec::with_capacity(page_count as usize);
+                for _i in 0..page_count {
+                    let mut row = Vec::with_capacity(column_count as usize);
+                    for _j in 0..column_count {
+                        let mut present_buf = [0u8; 1];
+                        storage.read(&mut present_buf)?;
+
+                        let mut min = [0u8; STATS_VALUE_SIZE];
+                        storage.read(&mut min)?;
+
+                        let mut max = [0u8; STATS_VALUE_SIZE];
+                        storage.read(&mut max)?;
+
+                        row.push(PageStats {
+                            present: present_buf[0] != 0,
+                            min,
+                            max,
+                        });
+                    }
+                    page_stats.push(row);
+                }
+                page_stats
+            }
+            _ => Vec::new(),
+        };
+
         Ok(Directory {
             header: DirectoryHeader {
                 magic,
@@ -138,6 +423,8 @@ impl Directory {
             },
             columns,
             names,
+            slots,
+            page_stats,
         })
     }
 
@@ -162,22 +449,132 @@ impl DirectoryColumn {
             name: string_to_fixed(name),
         }
     }
-    
+
     pub fn new_float(name: &str) -> Self {
         Self {
             type_: COLUMN_FLOAT,
             name: string_to_fixed(name),
         }
     }
-    
+
     pub fn new_string(name: &str) -> Self {
         Self {
             type_: COLUMN_STRING,
             name: string_to_fixed(name),
         }
     }
-    
+
     pub fn get_name(&self) -> String {
         fixed_to_string(&self.name)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pageman::MemStorage;
+    use alloc::vec;
+
+    fn stats_for(type_: u8, min: i64, max: i64) -> PageStats {
+        let mut stats = PageStats::from_field(type_, &min.to_string());
+        stats.max = PageStats::from_field(type_, &max.to_string()).min;
+        stats
+    }
+
+    #[test]
+    fn overlaps_eq_is_inclusive_at_both_boundaries() {
+        let stats = stats_for(COLUMN_INT, 10, 20);
+        assert!(Directory::overlaps(COLUMN_INT, &stats, &CompareOp::Eq(ColumnValue::Int(10))));
+        assert!(Directory::overlaps(COLUMN_INT, &stats, &CompareOp::Eq(ColumnValue::Int(20))));
+        assert!(Directory::overlaps(COLUMN_INT, &stats, &CompareOp::Eq(ColumnValue::Int(15))));
+        assert!(!Directory::overlaps(COLUMN_INT, &stats, &CompareOp::Eq(ColumnValue::Int(9))));
+        assert!(!Directory::overlaps(COLUMN_INT, &stats, &CompareOp::Eq(ColumnValue::Int(21))));
+    }
+
+    #[test]
+    fn overlaps_lt_excludes_at_min_boundary() {
+        let stats = stats_for(COLUMN_INT, 10, 20);
+        // `min == v` means every value on the page is `>= v`, so none of
+        // them can be `< v`: the page must be excluded right at the
+        // boundary, not just below it.
+        assert!(!Directory::overlaps(COLUMN_INT, &stats, &CompareOp::Lt(ColumnValue::Int(10))));
+        assert!(Directory::overlaps(COLUMN_INT, &stats, &CompareOp::Lt(ColumnValue::Int(11))));
+        assert!(!Directory::overlaps(COLUMN_INT, &stats, &CompareOp::Lt(ColumnValue::Int(9))));
+    }
+
+    #[test]
+    fn overlaps_gt_excludes_at_max_boundary() {
+        let stats = stats_for(COLUMN_INT, 10, 20);
+        // Symmetric with `Lt`: `max == v` means nothing on the page is `> v`.
+        assert!(!Directory::overlaps(COLUMN_INT, &stats, &CompareOp::Gt(ColumnValue::Int(20))));
+        assert!(Directory::overlaps(COLUMN_INT, &stats, &CompareOp::Gt(ColumnValue::Int(19))));
+        assert!(!Directory::overlaps(COLUMN_INT, &stats, &CompareOp::Gt(ColumnValue::Int(21))));
+    }
+
+    #[test]
+    fn overlaps_range_is_inclusive_and_detects_disjoint_ranges() {
+        let stats = stats_for(COLUMN_INT, 10, 20);
+        // Touching exactly at one edge still overlaps (both bounds inclusive).
+        assert!(Directory::overlaps(
+            COLUMN_INT,
+            &stats,
+            &CompareOp::Range(ColumnValue::Int(20), ColumnValue::Int(30))
+        ));
+        assert!(Directory::overlaps(
+            COLUMN_INT,
+            &stats,
+            &CompareOp::Range(ColumnValue::Int(0), ColumnValue::Int(10))
+        ));
+        // Strictly past either edge does not overlap.
+        assert!(!Directory::overlaps(
+            COLUMN_INT,
+            &stats,
+            &CompareOp::Range(ColumnValue::Int(21), ColumnValue::Int(30))
+        ));
+        assert!(!Directory::overlaps(
+            COLUMN_INT,
+            &stats,
+            &CompareOp::Range(ColumnValue::Int(0), ColumnValue::Int(9))
+        ));
+    }
+
+    #[test]
+    fn pages_matching_keeps_pages_with_no_stats_and_drops_all_for_unknown_column() {
+        let mut dir = Directory::new("d", Some(vec![DirectoryColumn::new_int("n")]));
+        dir.names.push(string_to_fixed("page0001"));
+        dir.slots.push(0);
+        dir.page_stats.push(vec![PageStats::none()]);
+        dir.header.page_count = 1;
+
+        // No stats for the column (e.g. unparseable): never skipped.
+        let matches = dir.pages_matching("n", &CompareOp::Eq(ColumnValue::Int(5)));
+        assert_eq!(matches, vec!["page0001".to_string()]);
+
+        // Unknown column name: nothing could be bounded, so nothing matches.
+        let matches = dir.pages_matching("missing", &CompareOp::Eq(ColumnValue::Int(5)));
+        assert!(matches.is_empty());
+    }
+
+    #[test]
+    fn read_from_tolerates_missing_zone_map_section() {
+        let mut dir = Directory::new("d", Some(vec![DirectoryColumn::new_int("n")]));
+        dir.names.push(string_to_fixed("page0001"));
+        dir.slots.push(0);
+        dir.page_stats.push(vec![stats_for(COLUMN_INT, 1, 1)]);
+        dir.header.page_count = 1;
+
+        let mut storage = MemStorage::new();
+        dir.write_to(&mut storage).unwrap();
+        let mut bytes = storage.into_bytes();
+
+        // Truncate off the zone-map section entirely (not even a
+        // `STATS_ABSENT` flag byte), so the file ends right after the
+        // slots table.
+        let stats_section_len = 1 + STATS_VALUE_SIZE * 2 + 1; // presence flag + one present/min/max triple
+        bytes.truncate(bytes.len() - stats_section_len);
+
+        let loaded = Directory::read_from(&mut MemStorage::from_bytes(bytes)).unwrap();
+        assert!(loaded.page_stats.is_empty());
+        assert_eq!(loaded.get_page_names(), vec!["page0001".to_string()]);
+    }
+}
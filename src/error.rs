@@ -1,14 +1,39 @@
-use std::fmt;
+use alloc::string::String;
+use core::fmt;
+
+/// The I/O error type carried by [`DbError::Io`]. Under the `std` feature
+/// this is just `std::io::Error`; without it there's no `std::io` to reuse,
+/// so `Storage` impls report the handful of cases this crate can actually
+/// hit.
+#[cfg(feature = "std")]
+pub type IoError = std::io::Error;
+
+#[cfg(not(feature = "std"))]
+#[derive(Debug)]
+pub enum IoError {
+    UnexpectedEof,
+}
+
+#[cfg(not(feature = "std"))]
+impl fmt::Display for IoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IoError::UnexpectedEof => write!(f, "unexpected end of storage"),
+        }
+    }
+}
 
 #[derive(Debug)]
 pub enum DbError {
     InvalidMagic {expected: u8, found: u8},
     InvalidPageCount(u8),
-    Io(std::io::Error),
+    Io(IoError),
     StringConversion(String),
     InvalidInput {expected: String, found: usize},
+    JournalCorrupt(String),
 }
 
+#[cfg(feature = "std")]
 impl From<std::io::Error> for DbError {
     fn from(error: std::io::Error) -> Self {
         DbError::Io(error)
@@ -23,10 +48,12 @@ impl fmt::Display for DbError {
             DbError::Io(err)                            => write!(f, "IO error: {}", err),
             DbError::StringConversion(msg)              => write!(f, "String conversion error: {}", msg),
             DbError::InvalidInput { expected, found }   => write!(f, "Page name '{}' exceeds maximum length of {}", expected, found),
+            DbError::JournalCorrupt(msg)                 => write!(f, "Journal corrupt: {}", msg),
         }
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for DbError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
@@ -36,4 +63,4 @@ impl std::error::Error for DbError {
     }
 }
 
-pub type Result<T> = std::result::Result<T, DbError>;
\ No newline at end of file
+pub type Result<T> = core::result::Result<T, DbError>;
@@ -1,9 +1,24 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![allow(non_snake_case)]
+
+extern crate alloc;
+
 pub mod common;
 pub mod error;
 pub mod pageman;
 pub mod dirman;
+#[cfg(feature = "std")]
+pub mod journal;
+pub mod codec;
 
 pub use common::{string_to_fixed, fixed_to_string};
 pub use error::{DbError, Result};
 pub use pageman::{Page, PageHeader, PAGE_CONTENT_SIZE};
-pub use dirman::{Directory, DirectoryColumn, DirectoryHeader, COLUMN_INT, COLUMN_FLOAT, COLUMN_STRING};
\ No newline at end of file
+pub use pageman::{Pager, Slot, Storage, MemStorage, PAGE_SLOT_STRIDE};
+pub use dirman::{Directory, DirectoryColumn, DirectoryHeader, COLUMN_INT, COLUMN_FLOAT, COLUMN_STRING};
+pub use dirman::{PageStats, ColumnValue, CompareOp};
+#[cfg(feature = "std")]
+pub use journal::{Journal, Transaction};
+pub use codec::{Codec, FixedLayoutCodec};
+#[cfg(feature = "serde")]
+pub use codec::BincodeCodec;
@@ -1,5 +1,5 @@
 use std::path::Path;
-use stDBMS::{Directory, DirectoryColumn, Page};
+use stDBMS::{Directory, DirectoryColumn, Page, Pager};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let columns = vec![
@@ -7,27 +7,27 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         DirectoryColumn::new_string("name"),
         DirectoryColumn::new_float("price"),
     ];
-    
+
     let mut dir = Directory::new("products", Some(columns));
-    
+    let mut pager = Pager::create(Path::new("products.pgr"))?;
+
     let page1 = Page::new("page1", b"1|Widget|19.99")?;
     let page2 = Page::new("page2", b"2|Gadget|29.99")?;
-    
-    dir.add_page(&page1)?;
-    dir.add_page(&page2)?;
-    
+
+    dir.add_page(&mut pager, &page1)?;
+    dir.add_page(&mut pager, &page2)?;
+
     dir.save(Path::new("products.dr"))?;
-    page1.save(Path::new("page1.pg"))?;
-    page2.save(Path::new("page2.pg"))?;
-    
-    println!("Directory '{}' created with {} pages", 
+
+    println!("Directory '{}' created with {} pages",
         dir.get_name(), dir.header.page_count);
-    
+
     let loaded_dir = Directory::load(Path::new("products.dr"))?;
-    let loaded_page = Page::load(Path::new("page1.pg"))?;
-    
+    let mut loaded_pager = Pager::open(Path::new("products.pgr"))?;
+    let loaded_page = loaded_pager.read_page(loaded_dir.slots[0])?;
+
     println!("Directory loaded: {}", loaded_dir.get_name());
     println!("Page content: {}", String::from_utf8_lossy(loaded_page.get_content()));
-    
+
     Ok(())
 }
\ No newline at end of file
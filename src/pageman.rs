@@ -1,8 +1,13 @@
-use std::fs::File;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::fs::{File, OpenOptions};
+#[cfg(feature = "std")]
 use std::io::{Read, Write};
+#[cfg(feature = "std")]
 use std::path::Path;
 use crate::common::{string_to_fixed, fixed_to_string, MIN_PAGE_NAME_SIZE};
-use crate::error::{DbError, Result};
+use crate::error::{DbError, IoError, Result};
 
 pub const PAGE_MAGIC: u8 = 0xCA;
 pub const PAGE_CONTENT_SIZE: usize = 256;
@@ -10,15 +15,171 @@ pub const PAGE_END: u8 = 0xED;
 pub const COLUMN_DELIMITER: u8 = 0xEE;
 pub const PAGE_NAME_SIZE: usize = MIN_PAGE_NAME_SIZE;
 
+/// Abstracts the byte medium `Page`/`Directory`/`Pager` are read from and
+/// written to, so the same encode/decode logic works against a real file
+/// (under the `std` feature) or an in-memory buffer (always available).
+// `len` reports the medium's byte size, not a collection length, so
+// there's no paired `is_empty` to add.
+#[allow(clippy::len_without_is_empty)]
+pub trait Storage {
+    fn read(&mut self, buf: &mut [u8]) -> Result<()>;
+    fn write(&mut self, buf: &[u8]) -> Result<()>;
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<()>;
+    fn write_at(&mut self, offset: u64, buf: &[u8]) -> Result<()>;
+    fn len(&mut self) -> Result<u64>;
+}
+
+fn unexpected_eof() -> IoError {
+    #[cfg(feature = "std")]
+    {
+        std::io::Error::from(std::io::ErrorKind::UnexpectedEof)
+    }
+    #[cfg(not(feature = "std"))]
+    {
+        IoError::UnexpectedEof
+    }
+}
+
+/// An in-memory `Storage` backend, growing as needed. Used for embedding
+/// these formats in other data, for tests, and as the default backend when
+/// the `std` feature is disabled.
+#[derive(Debug, Default, Clone)]
+pub struct MemStorage {
+    data: Vec<u8>,
+    pos: usize,
+}
+
+impl MemStorage {
+    pub fn new() -> Self {
+        MemStorage { data: Vec::new(), pos: 0 }
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> Self {
+        MemStorage { data, pos: 0 }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+impl Storage for MemStorage {
+    fn read(&mut self, buf: &mut [u8]) -> Result<()> {
+        let end = self.pos + buf.len();
+        if end > self.data.len() {
+            return Err(DbError::Io(unexpected_eof()));
+        }
+        buf.copy_from_slice(&self.data[self.pos..end]);
+        self.pos = end;
+        Ok(())
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<()> {
+        let end = self.pos + buf.len();
+        if end > self.data.len() {
+            self.data.resize(end, 0);
+        }
+        self.data[self.pos..end].copy_from_slice(buf);
+        self.pos = end;
+        Ok(())
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<()> {
+        let offset = offset as usize;
+        let end = offset + buf.len();
+        if end > self.data.len() {
+            return Err(DbError::Io(unexpected_eof()));
+        }
+        buf.copy_from_slice(&self.data[offset..end]);
+        Ok(())
+    }
+
+    fn write_at(&mut self, offset: u64, buf: &[u8]) -> Result<()> {
+        let offset = offset as usize;
+        let end = offset + buf.len();
+        if end > self.data.len() {
+            self.data.resize(end, 0);
+        }
+        self.data[offset..end].copy_from_slice(buf);
+        Ok(())
+    }
+
+    fn len(&mut self) -> Result<u64> {
+        Ok(self.data.len() as u64)
+    }
+}
+
+#[cfg(feature = "std")]
+impl Storage for File {
+    fn read(&mut self, buf: &mut [u8]) -> Result<()> {
+        Read::read_exact(self, buf)?;
+        Ok(())
+    }
+
+    fn write(&mut self, buf: &[u8]) -> Result<()> {
+        Write::write_all(self, buf)?;
+        Ok(())
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<()> {
+        file_read_at(self, offset, buf)?;
+        Ok(())
+    }
+
+    fn write_at(&mut self, offset: u64, buf: &[u8]) -> Result<()> {
+        file_write_at(self, offset, buf)?;
+        Ok(())
+    }
+
+    fn len(&mut self) -> Result<u64> {
+        Ok(self.metadata()?.len())
+    }
+}
+
+/// The `Storage` a bare `Pager` uses when no backend is named explicitly:
+/// a real file under `std`, otherwise the in-memory backend (there is no
+/// file system to fall back to).
+#[cfg(feature = "std")]
+pub type DefaultStorage = File;
+#[cfg(not(feature = "std"))]
+pub type DefaultStorage = MemStorage;
+
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct PageHeader {
     pub magic: u8,
     pub name: [u8; PAGE_NAME_SIZE],
 }
 
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PageHeader {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(serde::Deserialize)]
+        struct Raw {
+            magic: u8,
+            name: [u8; PAGE_NAME_SIZE],
+        }
+
+        let raw = Raw::deserialize(deserializer)?;
+        if raw.magic != PAGE_MAGIC {
+            return Err(serde::de::Error::custom(alloc::format!(
+                "invalid page magic: expected {:#x}, found {:#x}",
+                PAGE_MAGIC, raw.magic
+            )));
+        }
+
+        Ok(PageHeader { magic: raw.magic, name: raw.name })
+    }
+}
+
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Page {
     pub header: PageHeader,
+    #[cfg_attr(feature = "serde", serde(with = "crate::codec::fixed_bytes"))]
     pub content: [u8; PAGE_CONTENT_SIZE],
 }
 
@@ -33,7 +194,7 @@ impl Page {
 
         let mut content = [0u8; PAGE_CONTENT_SIZE];
         let copy_len = buffer.len().min(PAGE_CONTENT_SIZE - 1);
-        
+
         content[..copy_len].copy_from_slice(&buffer[..copy_len]);
         content[copy_len] = PAGE_END;
 
@@ -46,36 +207,47 @@ impl Page {
         })
     }
 
+    #[cfg(feature = "std")]
     pub fn save(&self, path: &Path) -> Result<()> {
         let mut file = File::create(path)?;
-        
-        file.write_all(&[self.header.magic])?;
-        file.write_all(&self.header.name)?;
-        file.write_all(&self.content)?;
-        
-        Ok(())
+        self.write_to(&mut file)
     }
 
+    #[cfg(feature = "std")]
     pub fn load(path: &Path) -> Result<Self> {
         let mut file = File::open(path)?;
-        
+        Self::read_from(&mut file)
+    }
+
+    /// Writes this page's fixed on-disk layout (magic + name + content) to
+    /// any `Storage`, so `save` and the in-memory `Codec`s share one encoder.
+    pub(crate) fn write_to<S: Storage>(&self, storage: &mut S) -> Result<()> {
+        storage.write(&[self.header.magic])?;
+        storage.write(&self.header.name)?;
+        storage.write(&self.content)?;
+        Ok(())
+    }
+
+    /// Reads this page's fixed on-disk layout from any `Storage`, so `load`
+    /// and the in-memory `Codec`s share one decoder.
+    pub(crate) fn read_from<S: Storage>(storage: &mut S) -> Result<Self> {
         let mut magic_buf = [0u8; 1];
-        file.read_exact(&mut magic_buf)?;
+        storage.read(&mut magic_buf)?;
         let magic = magic_buf[0];
-        
+
         let mut name = [0u8; PAGE_NAME_SIZE];
-        file.read_exact(&mut name)?;
-        
+        storage.read(&mut name)?;
+
         if magic != PAGE_MAGIC {
             return Err(DbError::InvalidMagic {
                 expected: PAGE_MAGIC,
                 found: magic,
             });
         }
-        
+
         let mut content = [0u8; PAGE_CONTENT_SIZE];
-        file.read_exact(&mut content)?;
-        
+        storage.read(&mut content)?;
+
         Ok(Page {
             header: PageHeader { magic, name },
             content,
@@ -92,4 +264,244 @@ impl Page {
             .unwrap_or(PAGE_CONTENT_SIZE);
         &self.content[..end_pos]
     }
-}
\ No newline at end of file
+}
+
+pub const PAGER_MAGIC: u8 = 0xCB;
+pub const PAGER_HEADER_SIZE: usize = 1;
+/// A page's on-disk layout (magic + name + content) is exactly one slot.
+pub const PAGE_SLOT_STRIDE: usize = 1 + PAGE_NAME_SIZE + PAGE_CONTENT_SIZE;
+
+/// Index of a page's fixed-size slot within a `Pager` file.
+pub type Slot = u32;
+
+/// All pages of a directory packed into one `Storage` as fixed-size slots,
+/// so a single page can be read or rewritten in place instead of every
+/// page living in its own file.
+pub struct Pager<S: Storage = DefaultStorage> {
+    storage: S,
+}
+
+impl<S: Storage> Pager<S> {
+    /// Wraps an already-initialized `Storage` (its header must already
+    /// have been written -- see `Pager::<File>::create`).
+    pub fn from_storage(storage: S) -> Self {
+        Pager { storage }
+    }
+
+    fn slot_offset(slot: Slot) -> u64 {
+        PAGER_HEADER_SIZE as u64 + slot as u64 * PAGE_SLOT_STRIDE as u64
+    }
+
+    /// Allocates the next free slot, i.e. the one just past the current
+    /// end of the storage. This is a pure calculation from the storage's
+    /// *current* length, not a reservation: call `write_page` on the
+    /// returned slot before allocating again, or a second call will hand
+    /// back the same slot and the second `write_page` will clobber the
+    /// first.
+    pub fn allocate_slot(&mut self) -> Result<Slot> {
+        let data_len = self.storage.len()?.saturating_sub(PAGER_HEADER_SIZE as u64);
+        Ok((data_len / PAGE_SLOT_STRIDE as u64) as Slot)
+    }
+
+    /// Allocates a slot and writes `page` into it in one step, so the two
+    /// can't be pulled apart and accidentally interleaved with another
+    /// `allocate_slot` call. Prefer this over `allocate_slot` + `write_page`
+    /// unless the slot is needed before the page is ready to write.
+    pub fn write_new_page(&mut self, page: &Page) -> Result<Slot> {
+        let slot = self.allocate_slot()?;
+        self.write_page(slot, page)?;
+        Ok(slot)
+    }
+
+    /// Writes `page` into `slot` in place, without touching any other slot.
+    pub fn write_page(&mut self, slot: Slot, page: &Page) -> Result<()> {
+        let mut buf = [0u8; PAGE_SLOT_STRIDE];
+        buf[0] = page.header.magic;
+        buf[1..1 + PAGE_NAME_SIZE].copy_from_slice(&page.header.name);
+        buf[1 + PAGE_NAME_SIZE..].copy_from_slice(&page.content);
+
+        self.storage.write_at(Self::slot_offset(slot), &buf)
+    }
+
+    /// Reads the raw slot bytes at `slot`, without validating them as a
+    /// `Page` (the slot may be unallocated or mid-write). Slots past the
+    /// current end of storage read back as all-zero, which is what an
+    /// un-journaled pre-image of a never-written slot should look like.
+    #[cfg(feature = "std")]
+    pub(crate) fn read_slot_raw(&mut self, slot: Slot) -> Result<[u8; PAGE_SLOT_STRIDE]> {
+        let offset = Self::slot_offset(slot);
+        let len = self.storage.len()?;
+
+        let mut buf = [0u8; PAGE_SLOT_STRIDE];
+        if offset + PAGE_SLOT_STRIDE as u64 <= len {
+            self.storage.read_at(offset, &mut buf)?;
+        }
+        Ok(buf)
+    }
+
+    /// Overwrites `slot` with raw bytes, bypassing `Page` validation. Used
+    /// by journal recovery to restore an exact pre-image.
+    #[cfg(feature = "std")]
+    pub(crate) fn write_slot_raw(&mut self, slot: Slot, bytes: &[u8; PAGE_SLOT_STRIDE]) -> Result<()> {
+        self.storage.write_at(Self::slot_offset(slot), bytes)
+    }
+
+    /// Reads the page stored at `slot`.
+    pub fn read_page(&mut self, slot: Slot) -> Result<Page> {
+        let mut buf = [0u8; PAGE_SLOT_STRIDE];
+        self.storage.read_at(Self::slot_offset(slot), &mut buf)?;
+
+        let magic = buf[0];
+        if magic != PAGE_MAGIC {
+            return Err(DbError::InvalidMagic {
+                expected: PAGE_MAGIC,
+                found: magic,
+            });
+        }
+
+        let mut name = [0u8; PAGE_NAME_SIZE];
+        name.copy_from_slice(&buf[1..1 + PAGE_NAME_SIZE]);
+
+        let mut content = [0u8; PAGE_CONTENT_SIZE];
+        content.copy_from_slice(&buf[1 + PAGE_NAME_SIZE..]);
+
+        Ok(Page {
+            header: PageHeader { magic, name },
+            content,
+        })
+    }
+}
+
+impl Pager<MemStorage> {
+    pub fn in_memory() -> Result<Self> {
+        let mut storage = MemStorage::new();
+        storage.write(&[PAGER_MAGIC])?;
+        Ok(Pager { storage })
+    }
+}
+
+#[cfg(feature = "std")]
+impl Pager<File> {
+    pub fn create(path: &Path) -> Result<Self> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        file.write_all(&[PAGER_MAGIC])?;
+        Ok(Pager { storage: file })
+    }
+
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+
+        let mut magic_buf = [0u8; 1];
+        file.read_exact(&mut magic_buf)?;
+        if magic_buf[0] != PAGER_MAGIC {
+            return Err(DbError::InvalidMagic {
+                expected: PAGER_MAGIC,
+                found: magic_buf[0],
+            });
+        }
+
+        Ok(Pager { storage: file })
+    }
+
+    /// Flushes the data file to disk. The journal calls this on commit,
+    /// before writing the commit marker and truncating itself (so a
+    /// committed write survives a crash even if the journal is already
+    /// gone).
+    pub(crate) fn sync(&mut self) -> Result<()> {
+        self.storage.sync_all()?;
+        Ok(())
+    }
+}
+
+/// Positioned read, backed by `FileExt::read_at`/`seek_read` where the
+/// platform offers it. Only the Unix path leaves the file's shared cursor
+/// untouched; `seek_read` and the seek fallback move it.
+#[cfg(all(feature = "std", unix))]
+fn file_read_at(file: &mut File, offset: u64, buf: &mut [u8]) -> std::io::Result<()> {
+    use std::os::unix::fs::FileExt;
+    file.read_exact_at(buf, offset)
+}
+
+#[cfg(all(feature = "std", windows))]
+fn file_read_at(file: &mut File, offset: u64, buf: &mut [u8]) -> std::io::Result<()> {
+    use std::os::windows::fs::FileExt;
+    let mut read = 0;
+    while read < buf.len() {
+        let n = file.seek_read(&mut buf[read..], offset + read as u64)?;
+        if n == 0 {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof));
+        }
+        read += n;
+    }
+    Ok(())
+}
+
+#[cfg(all(feature = "std", not(any(unix, windows))))]
+fn file_read_at(file: &mut File, offset: u64, buf: &mut [u8]) -> std::io::Result<()> {
+    use std::io::{Seek, SeekFrom};
+    file.seek(SeekFrom::Start(offset))?;
+    file.read_exact(buf)
+}
+
+/// Positioned write, backed by `FileExt::write_at`/`seek_write` where the
+/// platform offers it. Only the Unix path leaves the file's shared cursor
+/// untouched; `seek_write` and the seek fallback move it.
+#[cfg(all(feature = "std", unix))]
+fn file_write_at(file: &mut File, offset: u64, buf: &[u8]) -> std::io::Result<()> {
+    use std::os::unix::fs::FileExt;
+    file.write_all_at(buf, offset)
+}
+
+#[cfg(all(feature = "std", windows))]
+fn file_write_at(file: &mut File, offset: u64, buf: &[u8]) -> std::io::Result<()> {
+    use std::os::windows::fs::FileExt;
+    let mut written = 0;
+    while written < buf.len() {
+        let n = file.seek_write(&buf[written..], offset + written as u64)?;
+        if n == 0 {
+            return Err(std::io::Error::from(std::io::ErrorKind::WriteZero));
+        }
+        written += n;
+    }
+    Ok(())
+}
+
+#[cfg(all(feature = "std", not(any(unix, windows))))]
+fn file_write_at(file: &mut File, offset: u64, buf: &[u8]) -> std::io::Result<()> {
+    use std::io::{Seek, SeekFrom};
+    file.seek(SeekFrom::Start(offset))?;
+    file.write_all(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_new_page_allocates_a_fresh_slot_each_call() {
+        let mut pager = Pager::in_memory().unwrap();
+        let slot_a = pager.write_new_page(&Page::new("page0001", b"a").unwrap()).unwrap();
+        let slot_b = pager.write_new_page(&Page::new("page0002", b"b").unwrap()).unwrap();
+
+        assert_ne!(slot_a, slot_b);
+        assert_eq!(pager.read_page(slot_a).unwrap().get_content(), b"a");
+        assert_eq!(pager.read_page(slot_b).unwrap().get_content(), b"b");
+    }
+
+    #[test]
+    fn allocate_slot_without_an_intervening_write_repeats_the_same_slot() {
+        // Documents the footgun directly: `allocate_slot` is a pure
+        // calculation from the current storage length, not a reservation,
+        // so calling it twice before writing either slot hands back the
+        // same index both times.
+        let mut pager = Pager::in_memory().unwrap();
+        let first = pager.allocate_slot().unwrap();
+        let second = pager.allocate_slot().unwrap();
+        assert_eq!(first, second);
+    }
+}